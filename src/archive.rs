@@ -0,0 +1,96 @@
+use std::fs::File;
+use std::io::{ self, Read, Write };
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use tar::Archive;
+use zip::ZipArchive;
+
+use crate::{ FileCat, Filters };
+
+/// Returns true if `path`'s name marks it as a container this module can open.
+pub fn is_archive(path: &Path) -> bool {
+    let name = path.to_string_lossy().to_lowercase();
+    name.ends_with(".tar") || name.ends_with(".tar.gz") || name.ends_with(".tgz") || name.ends_with(".zip")
+}
+
+/// Descends into a tar/tar.gz/tgz/zip archive at `path`, printing each member
+/// through the same header + content pipeline used for files on disk, with a
+/// synthetic `archive.tar::path/inside.txt` path substituted into the header.
+/// Members are subject to the same `filters` (glob exclude + extension
+/// include/exclude) as files found on disk.
+pub fn process_archive<W: Write>(
+    filecat: &FileCat,
+    path: &Path,
+    filters: &Filters,
+    output: &mut W
+) -> io::Result<()> {
+    let name = path.to_string_lossy().to_lowercase();
+    let file = File::open(path)?;
+
+    if name.ends_with(".zip") {
+        process_zip(filecat, path, filters, file, output)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        process_tar(filecat, path, filters, Archive::new(GzDecoder::new(file)), output)
+    } else {
+        process_tar(filecat, path, filters, Archive::new(file), output)
+    }
+}
+
+fn process_tar<R: Read, W: Write>(
+    filecat: &FileCat,
+    archive_path: &Path,
+    filters: &Filters,
+    mut archive: Archive<R>,
+    output: &mut W
+) -> io::Result<()> {
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let inner_path = entry.path()?.into_owned();
+        if filters.is_excluded(&inner_path) || !filters.passes_ext_filter(&inner_path) {
+            continue;
+        }
+
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+
+        let synthetic_path = format!("{}::{}", archive_path.display(), inner_path.display());
+        filecat.print_entry(&synthetic_path, &content, output)?;
+    }
+    Ok(())
+}
+
+fn process_zip<W: Write>(
+    filecat: &FileCat,
+    archive_path: &Path,
+    filters: &Filters,
+    file: File,
+    output: &mut W
+) -> io::Result<()> {
+    let mut archive = ZipArchive::new(file).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    for i in 0..archive.len() {
+        let mut zip_entry = archive
+            .by_index(i)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if zip_entry.is_dir() {
+            continue;
+        }
+
+        let inner_path = Path::new(zip_entry.name()).to_path_buf();
+        if filters.is_excluded(&inner_path) || !filters.passes_ext_filter(&inner_path) {
+            continue;
+        }
+
+        let mut content = Vec::new();
+        zip_entry.read_to_end(&mut content)?;
+
+        let synthetic_path = format!("{}::{}", archive_path.display(), inner_path.display());
+        filecat.print_entry(&synthetic_path, &content, output)?;
+    }
+    Ok(())
+}
@@ -1,9 +1,16 @@
+mod archive;
+
 use clap::Parser;
 use colored::*;
+use glob::Pattern;
+use ignore::gitignore::{ Gitignore, GitignoreBuilder };
+use ignore::Match;
+use rayon::prelude::*;
 use std::collections::HashSet;
 use std::fs;
 use std::io::{ self, Read, Write };
 use std::path::{ Path, PathBuf };
+use std::sync::atomic::{ AtomicUsize, Ordering };
 
 /// Macro to print error messages with "error" colored red or "warning" colored yellow
 macro_rules! print_error {
@@ -56,10 +63,18 @@ struct Args {
     #[arg(short, long)]
     recursive: bool,
 
-    /// Exclude specific files or directories
+    /// Exclude specific files or directories (supports glob patterns, e.g. "**/*.lock")
     #[arg(short, long, value_name = "PATH")]
     exclude: Vec<String>,
 
+    /// Only include files with one of these extensions (e.g. "rs,toml")
+    #[arg(long, value_name = "EXT", value_delimiter = ',')]
+    include_ext: Vec<String>,
+
+    /// Exclude files with one of these extensions (e.g. "lock,bin")
+    #[arg(long, value_name = "EXT", value_delimiter = ',')]
+    exclude_ext: Vec<String>,
+
     /// Custom header format
     #[arg(long, default_value = "==> {file}")]
     header: String,
@@ -91,6 +106,100 @@ struct Args {
     /// Skip non-text files but still print headers
     #[arg(long)]
     skip_non_text: bool,
+
+    /// Descend into .tar, .tar.gz, .tgz, and .zip archives and print their members
+    #[arg(long)]
+    archives: bool,
+
+    /// Number of worker threads to process files with (default: available parallelism)
+    #[arg(long, value_name = "N")]
+    threads: Option<usize>,
+
+    /// Number of leading bytes sampled to decide if a file is text or binary
+    #[arg(long, value_name = "N", default_value_t = 8000)]
+    bytes: usize,
+
+    /// Number of bytes shown per row in --hex output
+    #[arg(long, value_name = "N", default_value_t = 16)]
+    hex_cols: usize,
+
+    /// Skip paths ignored by .gitignore files in the walked directories (and the global
+    /// git excludes file); .gitignore files above a starting directory are not read
+    #[arg(long)]
+    gitignore: bool,
+
+    /// Stop recursing after N directory levels
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+}
+
+/// Compiled include/exclude rules shared across a whole run
+pub(crate) struct Filters {
+    exclude_patterns: Vec<Pattern>,
+    include_ext: HashSet<String>,
+    exclude_ext: HashSet<String>,
+}
+
+impl Filters {
+    fn new(exclude: &[String], include_ext: &[String], exclude_ext: &[String], use_log_color: bool) -> Self {
+        let exclude_patterns = exclude
+            .iter()
+            .filter_map(|pattern| {
+                match Pattern::new(pattern) {
+                    Ok(pattern) => Some(pattern),
+                    Err(e) => {
+                        print_warning!(use_log_color, "Invalid exclude pattern {:?}: {}", pattern, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        let normalize = |exts: &[String]| -> HashSet<String> {
+            exts.iter()
+                .map(|ext| ext.trim_start_matches('.').to_lowercase())
+                .collect()
+        };
+
+        Filters {
+            exclude_patterns,
+            include_ext: normalize(include_ext),
+            exclude_ext: normalize(exclude_ext),
+        }
+    }
+
+    pub(crate) fn is_excluded(&self, path: &Path) -> bool {
+        self.exclude_patterns.iter().any(|pattern| pattern.matches_path(path))
+    }
+
+    pub(crate) fn passes_ext_filter(&self, path: &Path) -> bool {
+        let ext = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        if !self.include_ext.is_empty() && !self.include_ext.contains(&ext) {
+            return false;
+        }
+
+        !self.exclude_ext.contains(&ext)
+    }
+}
+
+/// Checks `path` against a stack of `.gitignore` matchers ordered from
+/// outermost (root/global) to innermost (nearest enclosing directory).
+/// The nearest matcher with an opinion wins; if it has none, rules fall
+/// through to the next ancestor up, mirroring git's cumulative semantics.
+fn is_gitignored(gitignore_stack: &[Gitignore], path: &Path) -> bool {
+    let is_dir = path.is_dir();
+    for gitignore in gitignore_stack.iter().rev() {
+        match gitignore.matched(path, is_dir) {
+            Match::Ignore(_) => return true,
+            Match::Whitelist(_) => return false,
+            Match::None => continue,
+        }
+    }
+    false
 }
 
 struct FileCat {
@@ -98,22 +207,31 @@ struct FileCat {
     verbose: bool,
     hex: bool,
     use_color: bool,
-    output: Option<PathBuf>,
     counter: bool,
     skip_non_text: bool,
-    file_count: usize,
+    archives: bool,
+    sample_bytes: usize,
+    hex_cols: usize,
+    use_gitignore: bool,
+    max_depth: Option<usize>,
+    file_count: AtomicUsize,
     use_log_color: bool,
 }
 
 impl FileCat {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         header: String,
         verbose: bool,
         hex: bool,
         use_color: bool,
-        output: Option<PathBuf>,
         counter: bool,
         skip_non_text: bool,
+        archives: bool,
+        sample_bytes: usize,
+        hex_cols: usize,
+        use_gitignore: bool,
+        max_depth: Option<usize>,
         use_log_color: bool
     ) -> Self {
         FileCat {
@@ -121,57 +239,161 @@ impl FileCat {
             verbose,
             hex,
             use_color,
-            output,
             counter,
             skip_non_text,
-            file_count: 0,
+            archives,
+            sample_bytes,
+            hex_cols,
+            use_gitignore,
+            max_depth,
+            file_count: AtomicUsize::new(0),
             use_log_color,
         }
     }
 
-    fn process_path(
-        &mut self,
+    /// Walks `path` (recursing into directories when `recursive` is set) and
+    /// appends every file or archive that survives `filters` to `entries`, in
+    /// traversal order. None of the collected files is opened at this stage.
+    fn collect_entries(
+        &self,
         path: &Path,
         recursive: bool,
-        exclude_set: &HashSet<PathBuf>,
-        output: &mut Box<dyn Write>
+        filters: &Filters,
+        entries: &mut Vec<PathBuf>
     ) -> io::Result<()> {
         if path.is_dir() {
-            self.process_dir(path, recursive, exclude_set, output)
-        } else if path.is_file() && !exclude_set.contains(path) {
-            self.process_file(path, output)
+            let mut gitignore_stack = Vec::new();
+            if let Some(global) = self.global_gitignore() {
+                gitignore_stack.push(global);
+            }
+            self.collect_dir(
+                path,
+                recursive,
+                filters,
+                entries,
+                self.max_depth.unwrap_or(usize::MAX),
+                &gitignore_stack
+            )
+        } else if path.is_file() {
+            if !filters.is_excluded(path) && filters.passes_ext_filter(path) {
+                entries.push(path.to_path_buf());
+            }
+            Ok(())
         } else {
             print_error!(self.use_log_color, "{} is not a valid file or directory", path.display());
             Ok(())
         }
     }
 
-    fn process_dir(
-        &mut self,
+    fn collect_dir(
+        &self,
         dir: &Path,
         recursive: bool,
-        exclude_set: &HashSet<PathBuf>,
-        output: &mut Box<dyn Write>
+        filters: &Filters,
+        entries: &mut Vec<PathBuf>,
+        depth: usize,
+        gitignore_stack: &[Gitignore]
     ) -> io::Result<()> {
+        let mut gitignore_stack = gitignore_stack.to_vec();
+        if let Some(local) = self.local_gitignore(dir) {
+            gitignore_stack.push(local);
+        }
+
         for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
-            if exclude_set.contains(&path) {
+            if filters.is_excluded(&path) {
+                continue;
+            }
+            if is_gitignored(&gitignore_stack, &path) {
                 continue;
             }
             if path.is_file() {
-                self.process_file(&path, output)?;
-            } else if recursive && path.is_dir() {
-                self.process_dir(&path, recursive, exclude_set, output)?;
+                if filters.passes_ext_filter(&path) {
+                    entries.push(path);
+                }
+            } else if recursive && path.is_dir() && depth > 0 {
+                self.collect_dir(&path, recursive, filters, entries, depth - 1, &gitignore_stack)?;
             }
         }
         Ok(())
     }
 
-    fn process_file(&mut self, file: &Path, output: &mut Box<dyn Write>) -> io::Result<()> {
+    /// Loads `dir`'s own `.gitignore`, if it has one. Ancestor rules are kept
+    /// separately (see `gitignore_stack` in `collect_dir`) and consulted
+    /// whenever the nearest `.gitignore` has no opinion on a path, matching
+    /// git's cumulative (not "nearest wins") semantics.
+    fn local_gitignore(&self, dir: &Path) -> Option<Gitignore> {
+        if !self.use_gitignore {
+            return None;
+        }
+
+        let local_path = dir.join(".gitignore");
+        if !local_path.is_file() {
+            return None;
+        }
+
+        let mut builder = GitignoreBuilder::new(dir);
+        if let Some(err) = builder.add(&local_path) {
+            print_warning!(self.use_log_color, "Failed to read {}: {}", local_path.display(), err);
+        }
+
+        match builder.build() {
+            Ok(gitignore) => Some(gitignore),
+            Err(e) => {
+                print_warning!(self.use_log_color, "Failed to parse {}: {}", local_path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Loads the user's global git excludes file (`core.excludesFile`'s usual
+    /// default location), if `--gitignore` is set and the file exists.
+    fn global_gitignore(&self) -> Option<Gitignore> {
+        if !self.use_gitignore {
+            return None;
+        }
+
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        let global_path = config_home.join("git").join("ignore");
+        if !global_path.is_file() {
+            return None;
+        }
+
+        let mut builder = GitignoreBuilder::new(".");
+        if let Some(err) = builder.add(&global_path) {
+            print_warning!(self.use_log_color, "Failed to read {}: {}", global_path.display(), err);
+        }
+        builder.build().ok()
+    }
+
+    /// Renders a single collected entry (a plain file, or an archive when
+    /// `--archives` is set) fully into `output`, its own private buffer.
+    fn render_entry<W: Write>(&self, path: &Path, filters: &Filters, output: &mut W) -> io::Result<()> {
+        if self.archives && archive::is_archive(path) {
+            archive::process_archive(self, path, filters, output)
+        } else {
+            self.process_file(path, output)
+        }
+    }
+
+    fn process_file<W: Write>(&self, file: &Path, output: &mut W) -> io::Result<()> {
         let mut file_content = Vec::new();
         fs::File::open(file)?.read_to_end(&mut file_content)?;
-        let header = self.header.replace("{file}", &file.display().to_string());
+        self.print_entry(&file.display().to_string(), &file_content, output)
+    }
+
+    /// Prints a header + content for a single logical entry, whether it came
+    /// from disk directly or from inside an archive (see the `archive` module).
+    pub(crate) fn print_entry<W: Write>(
+        &self,
+        display_path: &str,
+        content: &[u8],
+        output: &mut W
+    ) -> io::Result<()> {
+        let header = self.header.replace("{file}", display_path);
 
         if self.use_color {
             writeln!(output, "{}", header.blue().bold())?;
@@ -179,46 +401,86 @@ impl FileCat {
             writeln!(output, "{}", header)?;
         }
 
-        if !self.is_text_file(&file_content) {
+        if !self.is_text_file(content) {
             if self.skip_non_text {
                 writeln!(output, "Non-text file")?;
                 return Ok(());
             } else if self.hex {
-                self.print_hex(&file_content, output)?;
+                self.print_hex(content, output)?;
                 return Ok(());
             }
         }
 
-        self.print_content(&file_content, output)?;
+        self.print_content(content, output)?;
 
         if self.counter {
-            self.file_count += 1;
-            print_info!(self.use_log_color, "Files processed so far: {}", self.file_count);
+            let count = self.file_count.fetch_add(1, Ordering::Relaxed) + 1;
+            print_info!(self.use_log_color, "Files processed so far: {}", count);
         }
 
         Ok(())
     }
 
+    /// Git-style text/binary classification: look only at the first
+    /// `sample_bytes` of the file, call it binary on a NUL byte, and
+    /// otherwise accept it as text if it's valid UTF-8 or has only a small
+    /// fraction of non-text bytes (anything but printable ASCII or whitespace).
     fn is_text_file(&self, content: &[u8]) -> bool {
-        content
+        let sample_len = content.len().min(self.sample_bytes);
+        let sample = &content[..sample_len];
+
+        if sample.is_empty() {
+            return true;
+        }
+
+        if sample.contains(&0) {
+            return false;
+        }
+
+        if std::str::from_utf8(sample).is_ok() {
+            return true;
+        }
+
+        let non_text = sample
             .iter()
-            .all(|&byte| (byte.is_ascii_graphic() || byte.is_ascii_whitespace() || byte == b'\r'))
+            .filter(|&&byte| !(byte.is_ascii_graphic() || byte.is_ascii_whitespace()))
+            .count();
+
+        (non_text as f64) / (sample.len() as f64) < 0.3
     }
 
-    fn print_hex(&self, content: &[u8], output: &mut Box<dyn Write>) -> io::Result<()> {
-        for (i, byte) in content.iter().enumerate() {
-            if i % 16 == 0 {
-                if i != 0 {
-                    writeln!(output)?;
+    /// Canonical xxd-style hexdump: an 8-digit offset, `hex_cols` bytes per
+    /// row split after the first `hex_cols / 2` bytes with an extra space,
+    /// then a `|...|` ASCII gutter (printable bytes as themselves, `.` otherwise).
+    fn print_hex<W: Write>(&self, content: &[u8], output: &mut W) -> io::Result<()> {
+        let cols = self.hex_cols.max(1);
+        let half = cols / 2;
+
+        for (row_index, row) in content.chunks(cols).enumerate() {
+            write!(output, "{:08x}  ", row_index * cols)?;
+
+            for i in 0..cols {
+                if half > 0 && i == half {
+                    write!(output, " ")?;
+                }
+                match row.get(i) {
+                    Some(byte) => write!(output, "{:02x} ", byte)?,
+                    None => write!(output, "   ")?,
                 }
-                write!(output, "{:08x}  ", i)?;
             }
-            write!(output, "{:02x} ", byte)?;
+
+            write!(output, "|")?;
+            for &byte in row {
+                let ch = if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' };
+                write!(output, "{}", ch)?;
+            }
+            writeln!(output, "|")?;
         }
-        writeln!(output)
+
+        Ok(())
     }
 
-    fn print_content(&self, content: &[u8], output: &mut Box<dyn Write>) -> io::Result<()> {
+    fn print_content<W: Write>(&self, content: &[u8], output: &mut W) -> io::Result<()> {
         if self.verbose {
             write!(output, "{}", String::from_utf8_lossy(content))?;
         } else {
@@ -243,11 +505,12 @@ impl FileCat {
 
 fn main() -> io::Result<()> {
     let args = Args::parse();
-    let exclude_set: HashSet<PathBuf> = args.exclude.iter().map(PathBuf::from).collect();
 
     let use_log_color = !args.no_log_color;
     let use_color = args.color;
 
+    let filters = Filters::new(&args.exclude, &args.include_ext, &args.exclude_ext, use_log_color);
+
     if let Some(output_path) = &args.output {
         if output_path.is_dir() {
             print_error!(use_log_color, "Output path is a directory");
@@ -269,30 +532,61 @@ fn main() -> io::Result<()> {
         return Ok(());
     }
 
-    let mut viewer = FileCat::new(
+    let viewer = FileCat::new(
         args.header,
         args.verbose,
         args.hex,
         use_color,
-        args.output.clone(),
         args.counter,
         args.skip_non_text,
+        args.archives,
+        args.bytes,
+        args.hex_cols,
+        args.gitignore,
+        args.max_depth,
         use_log_color
     );
 
+    let mut entries = Vec::new();
+    for path in &args.paths {
+        viewer.collect_entries(Path::new(path), args.recursive, &filters, &mut entries)?;
+    }
+
+    let threads = args.threads.unwrap_or_else(|| {
+        std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    });
+    let pool = rayon::ThreadPoolBuilder
+        ::new()
+        .num_threads(threads)
+        .build()
+        .expect("failed to build the worker thread pool");
+
+    // Render every entry into its own buffer in parallel, then write the
+    // buffers out in the original traversal order so output stays
+    // deterministic and interleaving never corrupts a single entry's bytes.
+    let rendered: Vec<io::Result<Vec<u8>>> = pool.install(|| {
+        entries
+            .par_iter()
+            .map(|path| {
+                let mut buf = Vec::new();
+                viewer.render_entry(path, &filters, &mut buf)?;
+                Ok(buf)
+            })
+            .collect()
+    });
+
     let mut output: Box<dyn Write> = if let Some(output_path) = &args.output {
         Box::new(fs::File::create(output_path)?)
     } else {
         Box::new(io::stdout())
     };
 
-    for path in &args.paths {
-        let path = Path::new(path);
-        viewer.process_path(path, args.recursive, &exclude_set, &mut output)?;
+    for buf in rendered {
+        output.write_all(&buf?)?;
     }
 
     if args.counter {
-        print_info!(use_log_color, "Total files processed: {}", viewer.file_count);
+        print_info!(use_log_color, "Total files processed: {}", viewer.file_count.load(Ordering::Relaxed));
     }
 
     Ok(())